@@ -0,0 +1,635 @@
+//! An owned intermediate representation used by [`detach_buffered`](crate::detach_buffered).
+//!
+//! Unlike the zero-copy [`Deserializer`](crate::Deserializer) shim, this buffers the whole input
+//! into an owned [`Content`] tree first and then replays it into the target type.  That severs every
+//! lifetime tie to the original input, at the cost of requiring a self-describing format: the
+//! buffering step goes through [`deserialize_any`](serde::de::Deserializer::deserialize_any).
+
+use {
+    alloc::{boxed::Box, string::String, vec::Vec},
+    core::{fmt, marker::PhantomData},
+    serde::de,
+};
+
+/// A fully owned, self-describing deserialisation tree.
+///
+/// This is produced by [`Content`]'s [`Deserialize`](serde::Deserialize) implementation via
+/// [`deserialize_any`](serde::de::Deserializer::deserialize_any) and replayed by
+/// [`ContentDeserializer`].  Every borrowed value encountered while buffering is copied into an
+/// owned allocation, so the resulting tree is `'static`.
+#[derive(Debug, Clone)]
+pub enum Content {
+    Bool(bool),
+
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+
+    F32(f32),
+    F64(f64),
+
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+
+    None,
+    Some(Box<Content>),
+
+    Unit,
+    Newtype(Box<Content>),
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Enum {
+        variant: String,
+        data: Box<Content>,
+    },
+}
+
+impl<'de> de::Deserialize<'de> for Content {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl<'de> de::Visitor<'de> for ContentVisitor {
+    type Value = Content;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any self-describing value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Content::Bool(v))
+    }
+
+    fn visit_i8<E>(self, v: i8) -> Result<Self::Value, E> {
+        Ok(Content::I8(v))
+    }
+    fn visit_i16<E>(self, v: i16) -> Result<Self::Value, E> {
+        Ok(Content::I16(v))
+    }
+    fn visit_i32<E>(self, v: i32) -> Result<Self::Value, E> {
+        Ok(Content::I32(v))
+    }
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Content::I64(v))
+    }
+
+    fn visit_u8<E>(self, v: u8) -> Result<Self::Value, E> {
+        Ok(Content::U8(v))
+    }
+    fn visit_u16<E>(self, v: u16) -> Result<Self::Value, E> {
+        Ok(Content::U16(v))
+    }
+    fn visit_u32<E>(self, v: u32) -> Result<Self::Value, E> {
+        Ok(Content::U32(v))
+    }
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Content::U64(v))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E> {
+        Ok(Content::F32(v))
+    }
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Content::F64(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<Self::Value, E> {
+        Ok(Content::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Content::String(v.into()))
+    }
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Content::String(v.into()))
+    }
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Content::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Content::Bytes(v.into()))
+    }
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Content::Bytes(v.into()))
+    }
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Content::Bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(Content::None)
+    }
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        <Content as de::Deserialize>::deserialize(deserializer).map(|c| Content::Some(Box::new(c)))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(Content::Unit)
+    }
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        <Content as de::Deserialize>::deserialize(deserializer).map(|c| Content::Newtype(Box::new(c)))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        Ok(Content::Seq(elements))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Content::Map(entries))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        use de::VariantAccess as _;
+        let (variant, variant_access) = data.variant::<String>()?;
+        // The variant's payload is buffered back through `deserialize_any`, so a newtype payload
+        // materialises as the inner value, a tuple variant as a `Content::Seq` and a struct variant
+        // as a `Content::Map`; [`deserialize_enum`](ContentDeserializer::deserialize_enum) then
+        // replays each shape into the target.  Unit variants encoded through a dedicated
+        // [`EnumAccess`](de::EnumAccess) remain out of reach here, as serde's visitor API forces a
+        // single variant-kind choice without exposing the kind; in practice self-describing formats
+        // surface those as a bare `Content::String` via `deserialize_any` instead.
+        let data = variant_access.newtype_variant::<Content>()?;
+        Ok(Content::Enum {
+            variant,
+            data: Box::new(data),
+        })
+    }
+}
+
+/// Replays an owned [`Content`] tree into an arbitrary `'static` [`Visitor`](serde::de::Visitor).
+///
+/// Pair this with [`Content`]'s [`Deserialize`](serde::Deserialize) implementation to detach a value
+/// whose format hands back `'de`-bound data: first buffer into a [`Content`], then re-deserialise the
+/// target `T` from a `ContentDeserializer`.  This is exactly what [`detach_buffered`](crate::detach_buffered)
+/// does.
+pub struct ContentDeserializer<E> {
+    content: Content,
+    marker: PhantomData<fn() -> E>,
+}
+
+impl<E> ContentDeserializer<E> {
+    /// Wraps an owned [`Content`] so it can be handed to a [`Deserialize`](serde::Deserialize) impl.
+    pub fn new(content: Content) -> Self {
+        Self {
+            content,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> de::Deserializer<'static> for ContentDeserializer<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'static>,
+    {
+        match self.content {
+            Content::Bool(v) => visitor.visit_bool(v),
+
+            Content::U8(v) => visitor.visit_u8(v),
+            Content::U16(v) => visitor.visit_u16(v),
+            Content::U32(v) => visitor.visit_u32(v),
+            Content::U64(v) => visitor.visit_u64(v),
+
+            Content::I8(v) => visitor.visit_i8(v),
+            Content::I16(v) => visitor.visit_i16(v),
+            Content::I32(v) => visitor.visit_i32(v),
+            Content::I64(v) => visitor.visit_i64(v),
+
+            Content::F32(v) => visitor.visit_f32(v),
+            Content::F64(v) => visitor.visit_f64(v),
+
+            Content::Char(v) => visitor.visit_char(v),
+            Content::String(v) => visitor.visit_string(v),
+            Content::Bytes(v) => visitor.visit_byte_buf(v),
+
+            Content::None => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+
+            Content::Unit => visitor.visit_unit(),
+            Content::Newtype(v) => visitor.visit_newtype_struct(ContentDeserializer::new(*v)),
+            Content::Seq(v) => visitor.visit_seq(SeqAccess::new(v)),
+            Content::Map(v) => visitor.visit_map(MapAccess::new(v)),
+            Content::Enum { variant, data } => visitor.visit_enum(EnumAccess {
+                variant: Content::String(variant),
+                data: Some(*data),
+                marker: PhantomData,
+            }),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'static>,
+    {
+        match self.content {
+            Content::None => visitor.visit_none(),
+            Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+            Content::Unit => visitor.visit_unit(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'static>,
+    {
+        match self.content {
+            Content::Newtype(v) => visitor.visit_newtype_struct(ContentDeserializer::new(*v)),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'static>,
+    {
+        // Externally tagged enums are by far the most common encoding, and most self-describing
+        // formats surface them through `deserialize_any` as a single-entry map (`{variant: data}`)
+        // rather than as a dedicated `Content::Enum`.  Accept those shapes here, matching serde's
+        // own `ContentDeserializer`.
+        let (variant, data) = match self.content {
+            Content::Enum { variant, data } => (Content::String(variant), Some(*data)),
+            Content::Map(entries) => {
+                let mut iter = entries.into_iter();
+                let (variant, data) = match iter.next() {
+                    Some(entry) => entry,
+                    None => {
+                        return Err(de::Error::invalid_value(
+                            de::Unexpected::Map,
+                            &"map with a single key",
+                        ))
+                    }
+                };
+                if iter.next().is_some() {
+                    return Err(de::Error::invalid_value(
+                        de::Unexpected::Map,
+                        &"map with a single key",
+                    ));
+                }
+                (variant, Some(data))
+            }
+            Content::Seq(elements) => {
+                let mut iter = elements.into_iter();
+                let variant = match iter.next() {
+                    Some(variant) => variant,
+                    None => {
+                        return Err(de::Error::invalid_value(
+                            de::Unexpected::Seq,
+                            &"sequence carrying a variant tag",
+                        ))
+                    }
+                };
+                let data = iter.next();
+                if iter.next().is_some() {
+                    return Err(de::Error::invalid_value(
+                        de::Unexpected::Seq,
+                        &"sequence carrying at most a tag and its payload",
+                    ));
+                }
+                (variant, data)
+            }
+            Content::String(variant) => (Content::String(variant), None),
+            other => return Err(de::Error::invalid_type(unexpected(&other), &visitor)),
+        };
+        visitor.visit_enum(EnumAccess {
+            variant,
+            data,
+            marker: PhantomData,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        <V: Visitor<'static>>
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+fn unexpected(content: &Content) -> de::Unexpected<'_> {
+    match *content {
+        Content::Bool(b) => de::Unexpected::Bool(b),
+        Content::U8(n) => de::Unexpected::Unsigned(n.into()),
+        Content::U16(n) => de::Unexpected::Unsigned(n.into()),
+        Content::U32(n) => de::Unexpected::Unsigned(n.into()),
+        Content::U64(n) => de::Unexpected::Unsigned(n),
+        Content::I8(n) => de::Unexpected::Signed(n.into()),
+        Content::I16(n) => de::Unexpected::Signed(n.into()),
+        Content::I32(n) => de::Unexpected::Signed(n.into()),
+        Content::I64(n) => de::Unexpected::Signed(n),
+        Content::F32(f) => de::Unexpected::Float(f.into()),
+        Content::F64(f) => de::Unexpected::Float(f),
+        Content::Char(c) => de::Unexpected::Char(c),
+        Content::String(ref s) => de::Unexpected::Str(s),
+        Content::Bytes(ref b) => de::Unexpected::Bytes(b),
+        Content::None | Content::Some(_) => de::Unexpected::Option,
+        Content::Unit => de::Unexpected::Unit,
+        Content::Newtype(_) => de::Unexpected::NewtypeStruct,
+        Content::Seq(_) => de::Unexpected::Seq,
+        Content::Map(_) => de::Unexpected::Map,
+        Content::Enum { .. } => de::Unexpected::Enum,
+    }
+}
+
+struct SeqAccess<E> {
+    iter: alloc::vec::IntoIter<Content>,
+    marker: PhantomData<fn() -> E>,
+}
+impl<E> SeqAccess<E> {
+    fn new(elements: Vec<Content>) -> Self {
+        Self {
+            iter: elements.into_iter(),
+            marker: PhantomData,
+        }
+    }
+}
+impl<E> de::SeqAccess<'static> for SeqAccess<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'static>,
+    {
+        match self.iter.next() {
+            Some(content) => seed.deserialize(ContentDeserializer::new(content)).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapAccess<E> {
+    iter: alloc::vec::IntoIter<(Content, Content)>,
+    value: Option<Content>,
+    marker: PhantomData<fn() -> E>,
+}
+impl<E> MapAccess<E> {
+    fn new(entries: Vec<(Content, Content)>) -> Self {
+        Self {
+            iter: entries.into_iter(),
+            value: None,
+            marker: PhantomData,
+        }
+    }
+}
+impl<E> de::MapAccess<'static> for MapAccess<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'static>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ContentDeserializer::new(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'static>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("`next_value_seed` called before `next_key_seed`");
+        seed.deserialize(ContentDeserializer::new(value))
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumAccess<E> {
+    variant: Content,
+    data: Option<Content>,
+    marker: PhantomData<fn() -> E>,
+}
+impl<E> de::EnumAccess<'static> for EnumAccess<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    type Variant = VariantAccess<E>;
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'static>,
+    {
+        let variant = seed.deserialize(ContentDeserializer::new(self.variant))?;
+        Ok((
+            variant,
+            VariantAccess {
+                data: self.data,
+                marker: PhantomData,
+            },
+        ))
+    }
+}
+
+struct VariantAccess<E> {
+    data: Option<Content>,
+    marker: PhantomData<fn() -> E>,
+}
+impl<E> de::VariantAccess<'static> for VariantAccess<E>
+where
+    E: de::Error,
+{
+    type Error = E;
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.data {
+            None | Some(Content::Unit) => Ok(()),
+            Some(other) => Err(de::Error::invalid_type(unexpected(&other), &"unit variant")),
+        }
+    }
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'static>,
+    {
+        match self.data {
+            Some(data) => seed.deserialize(ContentDeserializer::new(data)),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'static>,
+    {
+        match self.data {
+            Some(Content::Seq(elements)) => visitor.visit_seq(SeqAccess::new(elements)),
+            Some(other) => Err(de::Error::invalid_type(unexpected(&other), &"tuple variant")),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'static>,
+    {
+        match self.data {
+            Some(Content::Map(entries)) => visitor.visit_map(MapAccess::new(entries)),
+            Some(Content::Seq(elements)) => visitor.visit_seq(SeqAccess::new(elements)),
+            Some(other) => Err(de::Error::invalid_type(unexpected(&other), &"struct variant")),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {
+        crate::detach_buffered,
+        alloc::{string::String, vec, vec::Vec},
+        serde::Deserialize,
+        serde_json::Deserializer,
+    };
+
+    /// Buffers `json` into a [`Content`](super::Content) and replays it into `T`, exercising the
+    /// whole `deserialize_any` → [`ContentDeserializer`](super::ContentDeserializer) round-trip.
+    fn roundtrip<T: Deserialize<'static>>(json: &str) -> T {
+        detach_buffered(&mut Deserializer::from_str(json)).unwrap()
+    }
+
+    #[test]
+    fn scalars() {
+        assert!(roundtrip::<bool>("true"));
+        assert_eq!(roundtrip::<i64>("-17"), -17);
+        assert_eq!(roundtrip::<u32>("42"), 42);
+        assert_eq!(roundtrip::<f64>("1.5"), 1.5);
+        assert_eq!(roundtrip::<String>("\"owned\""), "owned");
+    }
+
+    #[test]
+    fn seq() {
+        assert_eq!(roundtrip::<Vec<i32>>("[1, 2, 3]"), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn option() {
+        assert_eq!(roundtrip::<Option<i32>>("null"), None);
+        assert_eq!(roundtrip::<Option<i32>>("7"), Some(7));
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct {
+        name: String,
+        count: u8,
+    }
+
+    #[test]
+    fn map() {
+        assert_eq!(
+            roundtrip::<Struct>("{\"name\": \"x\", \"count\": 3}"),
+            Struct {
+                name: "x".into(),
+                count: 3,
+            }
+        );
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum {
+        Unit,
+        Newtype(i32),
+        Tuple(i32, i32),
+        Struct { a: i32 },
+    }
+
+    #[test]
+    fn externally_tagged_enum() {
+        assert_eq!(roundtrip::<Enum>("\"Unit\""), Enum::Unit);
+        assert_eq!(roundtrip::<Enum>("{\"Newtype\": 9}"), Enum::Newtype(9));
+        assert_eq!(
+            roundtrip::<Enum>("{\"Tuple\": [1, 2]}"),
+            Enum::Tuple(1, 2)
+        );
+        assert_eq!(
+            roundtrip::<Enum>("{\"Struct\": {\"a\": 4}}"),
+            Enum::Struct { a: 4 }
+        );
+    }
+}
